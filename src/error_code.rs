@@ -0,0 +1,64 @@
+//! S3 error codes
+//!
+//! See <https://docs.aws.amazon.com/AmazonS3/latest/API/ErrorResponses.html> for the full
+//! error code list and the HTTP status each one is reported with.
+
+use hyper::StatusCode;
+use std::fmt;
+
+/// S3 error code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum S3ErrorCode {
+    AccessDenied,
+    BucketAlreadyExists,
+    BucketAlreadyOwnedByYou,
+    EntityTooLarge,
+    InvalidArgument,
+    InvalidRange,
+    MalformedXML,
+    MethodNotAllowed,
+    NoSuchBucket,
+    NoSuchKey,
+    PreconditionFailed,
+    SignatureDoesNotMatch,
+}
+
+impl S3ErrorCode {
+    /// the HTTP status this error code is reported with
+    #[must_use]
+    pub const fn as_status_code(self) -> Option<StatusCode> {
+        let status = match self {
+            Self::AccessDenied | Self::SignatureDoesNotMatch => StatusCode::FORBIDDEN,
+            Self::BucketAlreadyExists | Self::BucketAlreadyOwnedByYou => StatusCode::CONFLICT,
+            Self::EntityTooLarge | Self::InvalidArgument | Self::MalformedXML => {
+                StatusCode::BAD_REQUEST
+            }
+            Self::InvalidRange => StatusCode::RANGE_NOT_SATISFIABLE,
+            Self::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            Self::NoSuchBucket | Self::NoSuchKey => StatusCode::NOT_FOUND,
+            Self::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+        };
+        Some(status)
+    }
+}
+
+impl fmt::Display for S3ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::AccessDenied => "AccessDenied",
+            Self::BucketAlreadyExists => "BucketAlreadyExists",
+            Self::BucketAlreadyOwnedByYou => "BucketAlreadyOwnedByYou",
+            Self::EntityTooLarge => "EntityTooLarge",
+            Self::InvalidArgument => "InvalidArgument",
+            Self::InvalidRange => "InvalidRange",
+            Self::MalformedXML => "MalformedXML",
+            Self::MethodNotAllowed => "MethodNotAllowed",
+            Self::NoSuchBucket => "NoSuchBucket",
+            Self::NoSuchKey => "NoSuchKey",
+            Self::PreconditionFailed => "PreconditionFailed",
+            Self::SignatureDoesNotMatch => "SignatureDoesNotMatch",
+        };
+        f.write_str(s)
+    }
+}
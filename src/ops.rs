@@ -0,0 +1,71 @@
+//! request-to-DTO wiring shared by operation handlers
+//!
+//! [`crate::output`] only describes how an already-built DTO is turned into a response; these
+//! functions are where the incoming [`Request`] is inspected to build that DTO in the first
+//! place, and are called by the handler for each operation before it reaches into storage.
+
+use crate::{
+    utils::request::{ContentRange, CopySource},
+    utils::RequestExt,
+    xml, BoxStdError, Request,
+};
+
+use hyper::header::ToStrError;
+use serde::Deserialize;
+
+/// resolves the `Range` header of a `GetObject` request against the object's size
+///
+/// Returns `Ok(None)` when the request did not ask for a range, in which case the whole object
+/// should be returned with a `200 OK` status.
+///
+/// # Errors
+/// Returns `Err` if the `Range` header is not valid UTF-8, or if the range cannot be satisfied
+/// against `object_size` (its first byte lies at or past the end of the object). An end past the
+/// object is clamped to the last byte rather than rejected.
+pub fn resolve_get_object_range(
+    req: &Request,
+    object_size: u64,
+) -> Result<Option<ContentRange>, BoxStdError> {
+    let range = match req.extract_range()? {
+        Some(range) => range,
+        None => return Ok(None),
+    };
+    let resolved = range
+        .resolve(object_size)
+        .map_err(|e| Box::new(e) as BoxStdError)?;
+    Ok(Some(resolved))
+}
+
+/// parses the `<Delete>` XML body of a `DeleteObjects` request
+///
+/// # Errors
+/// Returns `Err` if `body` is not a well-formed `<Delete>` document
+pub fn parse_delete_request(body: &str) -> Result<xml::Delete, quick_xml::DeError> {
+    xml::from_xml(body)
+}
+
+/// extracts and parses the `x-amz-copy-source` header of a `CopyObject` request
+///
+/// # Errors
+/// Returns `Err` if the header is present but not valid UTF-8
+pub fn copy_source(req: &Request) -> Result<Option<CopySource<'_>>, ToStrError> {
+    req.extract_copy_source()
+}
+
+/// the subset of `GetBucket`/`ListObjects` query parameters needed to pick a response shape
+#[derive(Debug, Deserialize)]
+struct ListObjectsQuery {
+    /// `list-type=2` selects the `ListObjectsV2` response shape
+    #[serde(rename = "list-type", default)]
+    list_type: Option<u8>,
+}
+
+/// whether a `ListObjects`/`ListObjectsV2` request should be served the `ListObjectsV2` response
+/// shape, i.e. whether it carries `?list-type=2`
+///
+/// # Errors
+/// Returns `Err` if the query string is not well-formed
+pub fn is_list_objects_v2(req: &Request) -> Result<bool, serde_urlencoded::de::Error> {
+    let query: Option<ListObjectsQuery> = req.extract_query()?;
+    Ok(query.and_then(|q| q.list_type) == Some(2))
+}
@@ -8,15 +8,12 @@ use super::{
 };
 
 use crate::{
-    utils::{time, Apply, ResponseExt, XmlWriterExt},
-    BoxStdError, Response,
+    utils::{time, Apply, ResponseExt},
+    xml, BoxStdError, Response,
 };
 
-use hyper::{header, Body, StatusCode};
-use xml::{
-    common::XmlVersion,
-    writer::{EventWriter, XmlEvent},
-};
+use hyper::{header, header::HeaderName, Body, StatusCode};
+use serde::Serialize;
 
 /// Types which can be converted into a response
 pub trait S3Output {
@@ -54,37 +51,17 @@ fn wrap_output(f: impl FnOnce() -> Result<Response, BoxStdError>) -> S3Result<Re
     }
 }
 
-/// a typed `None`
-const NONE_CALLBACK: Option<fn(Body) -> Response> = None;
-
-/// helper function for generating xml response
-fn wrap_xml_output<F>(
-    f: F,
-    r: Option<impl FnOnce(Body) -> Response>,
-    cap: usize,
-) -> S3Result<Response>
-where
-    F: FnOnce(&mut EventWriter<&mut Vec<u8>>) -> Result<(), xml::writer::Error>,
-{
-    wrap_output(move || {
-        let mut body = Vec::with_capacity(cap);
-        {
-            let mut w = EventWriter::new(&mut body);
-            w.write(XmlEvent::StartDocument {
-                version: XmlVersion::Version10,
-                encoding: Some("UTF-8"),
-                standalone: None,
-            })?;
-
-            f(&mut w)?;
-        }
+/// serialize an XML DTO into a `200 OK` response body with the XML content-type
+fn xml_response<T: Serialize>(value: &T) -> S3Result<Response> {
+    xml_response_with_status(value, StatusCode::OK)
+}
 
-        let mut res = match r {
-            None => Response::new(Body::from(body)),
-            Some(r) => r(Body::from(body)),
-        };
+/// serialize an XML DTO into a response body with the XML content-type and given status
+fn xml_response_with_status<T: Serialize>(value: &T, status: StatusCode) -> S3Result<Response> {
+    wrap_output(|| {
+        let body = xml::to_xml_with_header(value).map_err(|e| Box::new(e) as BoxStdError)?;
+        let mut res = Response::new_with_status(Body::from(body), status);
         res.set_mime(&mime::TEXT_XML)?;
-
         Ok(res)
     })
 }
@@ -100,6 +77,10 @@ struct XmlErrorResponse {
     resource: Option<String>,
     /// request_id
     request_id: Option<String>,
+    /// region
+    region: Option<String>,
+    /// host_id, echoed as the `x-amz-id-2` header
+    host_id: Option<String>,
 }
 
 impl XmlErrorResponse {
@@ -110,32 +91,77 @@ impl XmlErrorResponse {
             message,
             resource: None,
             request_id: None,
+            region: None,
+            host_id: None,
         }
     }
 }
 
 impl S3Output for XmlErrorResponse {
     fn try_into_response(self) -> S3Result<Response> {
-        wrap_xml_output(
-            |w| {
-                w.stack("Error", |w| {
-                    w.opt_element("Code", Some(&self.code.to_string()))?;
-                    w.opt_element("Message", self.message.as_deref())?;
-                    w.opt_element("Resource", self.resource.as_deref())?;
-                    w.opt_element("RequestId", self.request_id.as_deref())?;
-                    Ok(())
-                })
-            },
-            Some(|body| {
-                let status = self
-                    .code
-                    .as_status_code()
-                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
-
-                Response::new_with_status(body, status)
-            }),
-            64,
-        )
+        let status = self
+            .code
+            .as_status_code()
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        let doc = xml::ErrorDocument {
+            code: self.code.to_string().into(),
+            message: self.message.map(Into::into),
+            resource: self.resource.map(Into::into),
+            request_id: self.request_id.clone().map(Into::into),
+            region: self.region.map(Into::into),
+            host_id: self.host_id.clone().map(Into::into),
+        };
+
+        let res = xml_response_with_status(&doc, status)?;
+        wrap_output(move || {
+            let mut res = res;
+            res.set_opt_header(
+                HeaderName::from_static("x-amz-request-id"),
+                self.request_id,
+            )?;
+            res.set_opt_header(HeaderName::from_static("x-amz-id-2"), self.host_id)?;
+            Ok(res)
+        })
+    }
+}
+
+mod copy_object {
+    //! [`CopyObject`](https://docs.aws.amazon.com/AmazonS3/latest/API/API_CopyObject.html)
+
+    use super::*;
+    use crate::dto::{CopyObjectError, CopyObjectOutput};
+
+    impl S3Output for CopyObjectError {
+        fn try_into_response(self) -> S3Result<Response> {
+            match self {}
+        }
+    }
+
+    impl S3Output for CopyObjectOutput {
+        fn try_into_response(self) -> S3Result<Response> {
+            wrap_output(|| {
+                let body = xml::CopyObjectResult {
+                    e_tag: self.e_tag.map(Into::into),
+                    // RFC 3339 timestamps are already valid ISO 8601 text, so no conversion
+                    // is needed here (unlike `Last-Modified`, which needs RFC 1123 text).
+                    last_modified: self.last_modified.map(Into::into),
+                };
+                let xml_body = xml::to_xml_with_header(&body)?;
+
+                let mut res = Response::new(Body::from(xml_body));
+                res.set_mime(&mime::TEXT_XML)?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-copy-source-version-id"),
+                    self.copy_source_version_id,
+                )?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-version-id"),
+                    self.version_id,
+                )?;
+                Ok(res)
+            })
+        }
     }
 }
 
@@ -199,9 +225,18 @@ mod delete_object {
 
     impl S3Output for DeleteObjectOutput {
         fn try_into_response(self) -> S3Result<Response> {
-            let res = Response::new(Body::empty());
-            // TODO: handle other fields
-            Ok(res)
+            wrap_output(|| {
+                let mut res = Response::new(Body::empty());
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-version-id"),
+                    self.version_id,
+                )?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-delete-marker"),
+                    self.delete_marker.map(|b| b.to_string()),
+                )?;
+                Ok(res)
+            })
         }
     }
 
@@ -212,6 +247,53 @@ mod delete_object {
     }
 }
 
+mod delete_objects {
+    //! [`DeleteObjects`](https://docs.aws.amazon.com/AmazonS3/latest/API/API_DeleteObjects.html)
+
+    use super::*;
+    use crate::dto::{DeleteObjectsError, DeleteObjectsOutput};
+
+    impl S3Output for DeleteObjectsError {
+        fn try_into_response(self) -> S3Result<Response> {
+            match self {}
+        }
+    }
+
+    impl S3Output for DeleteObjectsOutput {
+        fn try_into_response(self) -> S3Result<Response> {
+            // `Quiet` is a field of the `<Delete>` request body, not this output: the handler
+            // is expected to have already omitted `deleted` entries before building the output
+            // when the request was quiet.
+            let deleted = self
+                .deleted
+                .into_iter()
+                .flatten()
+                .map(|deleted| xml::DeletedObject {
+                    key: deleted.key.map(Into::into),
+                })
+                .collect();
+
+            let error = self
+                .errors
+                .into_iter()
+                .flatten()
+                .map(|err| xml::DeleteObjectError {
+                    key: err.key.map(Into::into),
+                    code: err.code.map(Into::into),
+                    message: err.message.map(Into::into),
+                })
+                .collect();
+
+            let body = xml::DeleteResult {
+                xmlns: xml::Xmlns,
+                deleted,
+                error,
+            };
+            xml_response(&body)
+        }
+    }
+}
+
 mod get_object {
     //! [`GetObject`](https://docs.aws.amazon.com/AmazonS3/latest/API/API_GetObject.html)
 
@@ -221,20 +303,43 @@ mod get_object {
     impl S3Output for GetObjectOutput {
         fn try_into_response(self) -> S3Result<Response> {
             wrap_output(|| {
-                let mut res = Response::new(Body::empty());
-                if let Some(body) = self.body {
-                    *res.body_mut() = Body::wrap_stream(body);
-                }
+                let body = match self.body {
+                    Some(body) => Body::wrap_stream(body),
+                    None => Body::empty(),
+                };
+
+                let status = if self.content_range.is_some() {
+                    StatusCode::PARTIAL_CONTENT
+                } else {
+                    StatusCode::OK
+                };
+
+                let mut res = Response::new_with_status(body, status);
+
+                res.set_opt_header(header::CONTENT_RANGE, self.content_range)?;
                 res.set_opt_header(
                     header::CONTENT_LENGTH,
                     self.content_length.map(|l| format!("{}", l)),
                 )?;
+                res.set_opt_header(header::ACCEPT_RANGES, Some("bytes".to_owned()))?;
                 res.set_opt_header(header::CONTENT_TYPE, self.content_type)?;
+                res.set_opt_header(header::CACHE_CONTROL, self.cache_control)?;
+                res.set_opt_header(header::CONTENT_DISPOSITION, self.content_disposition)?;
+                res.set_opt_header(header::CONTENT_ENCODING, self.content_encoding)?;
+                res.set_opt_header(header::CONTENT_LANGUAGE, self.content_language)?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-version-id"),
+                    self.version_id,
+                )?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-storage-class"),
+                    self.storage_class,
+                )?;
+                res.set_user_metadata(self.metadata)?;
 
                 res.set_opt_last_modified(time::map_opt_rfc3339_to_last_modified(
                     self.last_modified,
                 )?)?;
-                // TODO: handle other fields
                 Ok(res)
             })
         }
@@ -246,6 +351,9 @@ mod get_object {
                 Self::NoSuchKey(msg) => {
                     XmlErrorResponse::from_code_msg(S3ErrorCode::NoSuchKey, msg.into())
                 }
+                Self::InvalidRange(msg) => {
+                    XmlErrorResponse::from_code_msg(S3ErrorCode::InvalidRange, msg.into())
+                }
             };
             resp.try_into_response()
         }
@@ -260,16 +368,11 @@ mod get_bucket_location {
 
     impl S3Output for GetBucketLocationOutput {
         fn try_into_response(self) -> S3Result<Response> {
-            wrap_xml_output(
-                |w| {
-                    w.element(
-                        "LocationConstraint",
-                        self.location_constraint.as_deref().unwrap_or(""),
-                    )
-                },
-                NONE_CALLBACK,
-                4096,
-            )
+            let body = xml::LocationConstraint {
+                xmlns: xml::Xmlns,
+                value: self.location_constraint.unwrap_or_default(),
+            };
+            xml_response(&body)
         }
     }
 
@@ -324,7 +427,20 @@ mod head_object {
                 )?)?;
                 res.set_opt_header(header::ETAG, self.e_tag)?;
                 res.set_opt_header(header::EXPIRES, self.expires)?;
-                // TODO: handle other fields
+                res.set_opt_header(header::CACHE_CONTROL, self.cache_control)?;
+                res.set_opt_header(header::CONTENT_DISPOSITION, self.content_disposition)?;
+                res.set_opt_header(header::CONTENT_ENCODING, self.content_encoding)?;
+                res.set_opt_header(header::CONTENT_LANGUAGE, self.content_language)?;
+                res.set_opt_header(header::ACCEPT_RANGES, Some("bytes".to_owned()))?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-version-id"),
+                    self.version_id,
+                )?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-storage-class"),
+                    self.storage_class,
+                )?;
+                res.set_user_metadata(self.metadata)?;
                 Ok(res)
             })
         }
@@ -350,29 +466,23 @@ mod list_buckets {
 
     impl S3Output for ListBucketsOutput {
         fn try_into_response(self) -> S3Result<Response> {
-            wrap_xml_output(
-                |w| {
-                    w.stack("ListBucketsOutput", |w| {
-                        w.opt_stack("Buckets", self.buckets, |w, buckets| {
-                            for bucket in buckets {
-                                w.stack("Bucket", |w| {
-                                    w.opt_element("CreationDate", bucket.creation_date.as_deref())?;
-                                    w.opt_element("Name", bucket.name.as_deref())
-                                })?;
-                            }
-                            Ok(())
-                        })?;
-
-                        w.opt_stack("Owner", self.owner, |w, owner| {
-                            w.opt_element("DisplayName", owner.display_name.as_deref())?;
-                            w.opt_element("ID", owner.id.as_deref())
-                        })?;
-                        Ok(())
-                    })
-                },
-                NONE_CALLBACK,
-                4096,
-            )
+            let body = xml::ListAllMyBucketsResult {
+                xmlns: xml::Xmlns,
+                buckets: self.buckets.map(|buckets| xml::Buckets {
+                    bucket: buckets
+                        .into_iter()
+                        .map(|bucket| xml::Bucket {
+                            name: bucket.name.map(Into::into),
+                            creation_date: bucket.creation_date.map(Into::into),
+                        })
+                        .collect(),
+                }),
+                owner: self.owner.map(|owner| xml::Owner {
+                    id: owner.id.map(Into::into),
+                    display_name: owner.display_name.map(Into::into),
+                }),
+            };
+            xml_response(&body)
         }
     }
 
@@ -402,59 +512,122 @@ mod list_objects {
 
     impl S3Output for ListObjectsOutput {
         fn try_into_response(self) -> S3Result<Response> {
-            wrap_xml_output(
-                |w| {
-                    w.stack("ListBucketResult", |w| {
-                        w.opt_element(
-                            "IsTruncated",
-                            self.is_truncated.map(|b| b.to_string()).as_deref(),
-                        )?;
-                        w.opt_element("Marker", self.marker.as_deref())?;
-                        w.opt_element("NextMarker", self.next_marker.as_deref())?;
-                        if let Some(contents) = self.contents {
-                            for content in contents {
-                                w.stack("Contents", |w| {
-                                    w.opt_element("Key", content.key.as_deref())?;
-                                    w.opt_element(
-                                        "LastModified",
-                                        content.last_modified.as_deref(),
-                                    )?;
-                                    w.opt_element("ETag", content.e_tag.as_deref())?;
-                                    w.opt_element(
-                                        "Size",
-                                        content.size.map(|s| s.to_string()).as_deref(),
-                                    )?;
-                                    w.opt_element(
-                                        "StorageClass",
-                                        content.storage_class.as_deref(),
-                                    )?;
-                                    w.opt_stack("Owner", content.owner, |w, owner| {
-                                        w.opt_element("ID", owner.id.as_deref())?;
-                                        w.opt_element(
-                                            "DisplayName",
-                                            owner.display_name.as_deref(),
-                                        )?;
-                                        Ok(())
-                                    })
-                                })?;
-                            }
-                        }
-                        w.opt_element("Name", self.name.as_deref())?;
-                        w.opt_element("Prefix", self.prefix.as_deref())?;
-                        w.opt_element("Delimiter", self.delimiter.as_deref())?;
-                        w.opt_element("MaxKeys", self.max_keys.map(|k| k.to_string()).as_deref())?;
-                        w.opt_stack("CommonPrefixes", self.common_prefixes, |w, prefixes| {
-                            w.iter_element(prefixes.into_iter(), |w, common_prefix| {
-                                w.opt_element("Prefix", common_prefix.prefix.as_deref())
-                            })
-                        })?;
-                        w.opt_element("EncodingType", self.encoding_type.as_deref())?;
-                        Ok(())
+            let url_encode = self.encoding_type.as_deref() == Some("url");
+            let encode_key = |s: String| -> xml::Value {
+                if url_encode {
+                    xml::uri_encode(&s, true).into()
+                } else {
+                    s.into()
+                }
+            };
+
+            let body = xml::ListBucketResult {
+                xmlns: xml::Xmlns,
+                is_truncated: self.is_truncated.map(|b| b.to_string().into()),
+                marker: self.marker.map(encode_key),
+                next_marker: self.next_marker.map(encode_key),
+                contents: self
+                    .contents
+                    .into_iter()
+                    .flatten()
+                    .map(|content| xml::Contents {
+                        key: content.key.map(encode_key),
+                        last_modified: content.last_modified.map(Into::into),
+                        e_tag: content.e_tag.map(Into::into),
+                        size: content.size.map(xml::IntValue),
+                        storage_class: content.storage_class.map(Into::into),
+                        owner: content.owner.map(|owner| xml::Owner {
+                            id: owner.id.map(Into::into),
+                            display_name: owner.display_name.map(Into::into),
+                        }),
+                    })
+                    .collect(),
+                name: self.name.map(Into::into),
+                prefix: self.prefix.map(encode_key),
+                delimiter: self.delimiter.map(encode_key),
+                max_keys: self.max_keys.map(xml::IntValue),
+                common_prefixes: self
+                    .common_prefixes
+                    .into_iter()
+                    .flatten()
+                    .map(|common_prefix| xml::CommonPrefix {
+                        prefix: common_prefix.prefix.map(encode_key),
+                    })
+                    .collect(),
+                encoding_type: self.encoding_type.map(Into::into),
+            };
+            xml_response(&body)
+        }
+    }
+}
+
+mod list_objects_v2 {
+    //! [`ListObjectsV2`](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListObjectsV2.html)
+
+    use super::*;
+    use crate::dto::{ListObjectsV2Error, ListObjectsV2Output};
+
+    impl S3Output for ListObjectsV2Error {
+        fn try_into_response(self) -> S3Result<Response> {
+            let resp = match self {
+                Self::NoSuchBucket(msg) => {
+                    XmlErrorResponse::from_code_msg(S3ErrorCode::NoSuchBucket, msg.into())
+                }
+            };
+            resp.try_into_response()
+        }
+    }
+
+    impl S3Output for ListObjectsV2Output {
+        fn try_into_response(self) -> S3Result<Response> {
+            let url_encode = self.encoding_type.as_deref() == Some("url");
+            let encode_key = |s: String| -> xml::Value {
+                if url_encode {
+                    xml::uri_encode(&s, true).into()
+                } else {
+                    s.into()
+                }
+            };
+            let fetch_owner = self.fetch_owner.unwrap_or(false);
+
+            let body = xml::ListBucketResultV2 {
+                xmlns: xml::Xmlns,
+                is_truncated: self.is_truncated.map(|b| b.to_string().into()),
+                contents: self
+                    .contents
+                    .into_iter()
+                    .flatten()
+                    .map(|content| xml::Contents {
+                        key: content.key.map(encode_key),
+                        last_modified: content.last_modified.map(Into::into),
+                        e_tag: content.e_tag.map(Into::into),
+                        size: content.size.map(xml::IntValue),
+                        storage_class: content.storage_class.map(Into::into),
+                        owner: content.owner.filter(|_| fetch_owner).map(|owner| xml::Owner {
+                            id: owner.id.map(Into::into),
+                            display_name: owner.display_name.map(Into::into),
+                        }),
                     })
-                },
-                NONE_CALLBACK,
-                4096,
-            )
+                    .collect(),
+                name: self.name.map(Into::into),
+                prefix: self.prefix.map(encode_key),
+                delimiter: self.delimiter.map(encode_key),
+                max_keys: self.max_keys.map(xml::IntValue),
+                common_prefixes: self
+                    .common_prefixes
+                    .into_iter()
+                    .flatten()
+                    .map(|common_prefix| xml::CommonPrefix {
+                        prefix: common_prefix.prefix.map(encode_key),
+                    })
+                    .collect(),
+                encoding_type: self.encoding_type.map(Into::into),
+                key_count: self.key_count.map(xml::IntValue),
+                continuation_token: self.continuation_token.map(Into::into),
+                next_continuation_token: self.next_continuation_token.map(Into::into),
+                start_after: self.start_after.map(encode_key),
+            };
+            xml_response(&body)
         }
     }
 }
@@ -467,9 +640,23 @@ mod put_object {
 
     impl S3Output for PutObjectOutput {
         fn try_into_response(self) -> S3Result<Response> {
-            let res = Response::new(Body::empty());
-            // TODO: handle other fields
-            Ok(res)
+            wrap_output(|| {
+                let mut res = Response::new(Body::empty());
+                res.set_opt_header(header::ETAG, self.e_tag)?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-version-id"),
+                    self.version_id,
+                )?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-server-side-encryption"),
+                    self.server_side_encryption,
+                )?;
+                res.set_opt_header(
+                    HeaderName::from_static("x-amz-server-side-encryption-customer-algorithm"),
+                    self.sse_customer_algorithm,
+                )?;
+                Ok(res)
+            })
         }
     }
 
@@ -7,7 +7,150 @@ use hyper::{
     Body,
 };
 use serde::de::DeserializeOwned;
-use std::{mem, str::FromStr};
+use std::{fmt, mem, str::FromStr};
+
+/// a `Range` header, as requested by the client and not yet checked against an object size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// `bytes=N-` : from `N` to the end of the object
+    FromStart(u64),
+    /// `bytes=N-M` : from `N` to `M`, inclusive
+    Inclusive(u64, u64),
+    /// `bytes=-N` : the last `N` bytes of the object
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// Parses a `Range` header value, e.g. `bytes=0-499`
+    ///
+    /// Returns `None` for anything that is not a single, well-formed `bytes` range; per HTTP
+    /// semantics, a malformed or unsupported `Range` header should simply be ignored (the whole
+    /// object is returned), not treated as an error.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.strip_prefix("bytes=")?;
+        let (start, end) = s.split_once('-')?;
+
+        if start.is_empty() {
+            let suffix_len = end.parse().ok()?;
+            return Some(Self::Suffix(suffix_len));
+        }
+
+        let start = start.parse().ok()?;
+        if end.is_empty() {
+            return Some(Self::FromStart(start));
+        }
+
+        let end = end.parse().ok()?;
+        Some(Self::Inclusive(start, end))
+    }
+
+    /// Resolves this range against an object of `size` bytes
+    ///
+    /// # Errors
+    /// Returns `Err` if the range is unsatisfiable (e.g. `start` is at or past `size`)
+    pub fn resolve(self, size: u64) -> Result<ContentRange, InvalidRangeError> {
+        let (start, end) = match self {
+            Self::FromStart(start) => (start, size.saturating_sub(1)),
+            Self::Inclusive(start, end) => (start, end.min(size.saturating_sub(1))),
+            Self::Suffix(len) => (size.saturating_sub(len), size.saturating_sub(1)),
+        };
+
+        if size == 0 || start >= size || start > end {
+            return Err(InvalidRangeError);
+        }
+
+        Ok(ContentRange {
+            start,
+            end,
+            total: Some(size),
+        })
+    }
+}
+
+/// a byte range resolved against an object's size, ready to be written into `Content-Range`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    /// first byte of the range, inclusive
+    pub start: u64,
+    /// last byte of the range, inclusive
+    pub end: u64,
+    /// total size of the object, when known
+    pub total: Option<u64>,
+}
+
+impl ContentRange {
+    /// number of bytes covered by this range
+    #[must_use]
+    pub const fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// a `ContentRange` never covers zero bytes; always returns `false`
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// renders the `Content-Range` header value, e.g. `bytes 0-499/1234`
+    #[must_use]
+    pub fn header_value(&self) -> String {
+        match self.total {
+            Some(total) => format!("bytes {}-{}/{}", self.start, self.end, total),
+            None => format!("bytes {}-{}/*", self.start, self.end),
+        }
+    }
+}
+
+/// the requested range could not be satisfied against the object's size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidRangeError;
+
+impl fmt::Display for InvalidRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the requested range is not satisfiable")
+    }
+}
+
+impl std::error::Error for InvalidRangeError {}
+
+/// a parsed `x-amz-copy-source` header value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopySource<'a> {
+    /// source bucket name
+    pub bucket: &'a str,
+    /// source object key
+    pub key: &'a str,
+    /// source object version id, from a `?versionId=` suffix
+    pub version_id: Option<&'a str>,
+}
+
+impl<'a> CopySource<'a> {
+    /// Parses a `x-amz-copy-source` header value, e.g. `/bucket/key?versionId=abc`
+    ///
+    /// The leading `/` is optional; AWS accepts both `/bucket/key` and `bucket/key`.
+    #[must_use]
+    pub fn parse(s: &'a str) -> Option<Self> {
+        let s = s.strip_prefix('/').unwrap_or(s);
+        let (path, query) = match s.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (s, None),
+        };
+
+        let (bucket, key) = path.split_once('/')?;
+        if bucket.is_empty() || key.is_empty() {
+            return None;
+        }
+
+        let version_id = query.and_then(|q| q.strip_prefix("versionId="));
+
+        Some(Self {
+            bucket,
+            key,
+            version_id,
+        })
+    }
+}
 
 /// `RequestExt`
 pub trait RequestExt {
@@ -23,6 +166,12 @@ pub trait RequestExt {
     /// extract s3 path
     fn extract_s3_path(&self) -> Result<S3Path<'_>, ParseS3PathError>;
 
+    /// extract the `Range` header, if present and well-formed
+    fn extract_range(&self) -> Result<Option<ByteRange>, ToStrError>;
+
+    /// extract the `x-amz-copy-source` header, if present and well-formed
+    fn extract_copy_source(&self) -> Result<Option<CopySource<'_>>, ToStrError>;
+
     /// assign opt header
     fn assign_opt_header<T>(
         &self,
@@ -56,6 +205,20 @@ impl RequestExt for Request {
         S3Path::try_from_path(self.uri().path())
     }
 
+    fn extract_range(&self) -> Result<Option<ByteRange>, ToStrError> {
+        let range = self
+            .get_header_str(hyper::header::RANGE)?
+            .and_then(ByteRange::parse);
+        Ok(range)
+    }
+
+    fn extract_copy_source(&self) -> Result<Option<CopySource<'_>>, ToStrError> {
+        let source = self
+            .get_header_str("x-amz-copy-source")?
+            .and_then(CopySource::parse);
+        Ok(source)
+    }
+
     fn assign_opt_header<T>(
         &self,
         name: impl AsHeaderName,
@@ -0,0 +1,57 @@
+//! response util
+
+use crate::{BoxStdError, Response};
+
+use hyper::header::HeaderName;
+use std::{collections::HashMap, str::FromStr};
+
+/// `ResponseExt`
+pub trait ResponseExt {
+    /// set an optional header
+    fn set_opt_header(&mut self, name: HeaderName, value: Option<String>)
+        -> Result<(), BoxStdError>;
+
+    /// set the `Content-Type` header from a mime type
+    fn set_mime(&mut self, mime: &mime::Mime) -> Result<(), BoxStdError>;
+
+    /// set the `Last-Modified` header
+    fn set_opt_last_modified(&mut self, value: Option<String>) -> Result<(), BoxStdError>;
+
+    /// set an `x-amz-meta-*` header for each user metadata entry
+    fn set_user_metadata(
+        &mut self,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Result<(), BoxStdError>;
+}
+
+impl ResponseExt for Response {
+    fn set_opt_header(
+        &mut self,
+        name: HeaderName,
+        value: Option<String>,
+    ) -> Result<(), BoxStdError> {
+        if let Some(value) = value {
+            self.headers_mut().insert(name, value.parse()?);
+        }
+        Ok(())
+    }
+
+    fn set_mime(&mut self, mime: &mime::Mime) -> Result<(), BoxStdError> {
+        self.set_opt_header(hyper::header::CONTENT_TYPE, Some(mime.to_string()))
+    }
+
+    fn set_opt_last_modified(&mut self, value: Option<String>) -> Result<(), BoxStdError> {
+        self.set_opt_header(hyper::header::LAST_MODIFIED, value)
+    }
+
+    fn set_user_metadata(
+        &mut self,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Result<(), BoxStdError> {
+        for (key, value) in metadata.into_iter().flatten() {
+            let name = HeaderName::from_str(&format!("x-amz-meta-{}", key))?;
+            self.set_opt_header(name, Some(value))?;
+        }
+        Ok(())
+    }
+}
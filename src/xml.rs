@@ -0,0 +1,517 @@
+//! XML response shapes, serialized via `serde` + `quick_xml`
+//!
+//! Each S3 response body is modeled as a plain struct deriving
+//! [`serde::Serialize`], with field names pinned via `#[serde(rename = "...")]`
+//! to match the wire format exactly. [`to_xml_with_header`] serializes a
+//! value and prepends the XML declaration that every S3 response carries.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize, Serializer};
+
+/// the XML declaration prefixed to every S3 response body
+const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+/// serialize `value` and prefix it with the standard XML declaration
+pub fn to_xml_with_header<T: Serialize>(value: &T) -> Result<String, quick_xml::DeError> {
+    let mut buf = String::from(XML_HEADER);
+    buf.push_str(&quick_xml::se::to_string(value)?);
+    Ok(buf)
+}
+
+/// deserialize a request body shape from its XML representation
+pub fn from_xml<T: DeserializeOwned>(s: &str) -> Result<T, quick_xml::DeError> {
+    quick_xml::de::from_str(s)
+}
+
+/// percent-encode `s` per RFC 3986, leaving the unreserved set (`A-Z a-z 0-9 - _ . ~`) untouched
+///
+/// Used to implement `encoding-type=url` on `ListObjects`/`ListObjectsV2`: key-like fields are
+/// run through this before being written into the response so clients get back safely
+/// round-trippable bytes. Set `encode_slash` to `false` to leave `/` unescaped; the
+/// `ListObjects`/`ListObjectsV2` outputs pass `true`, so `/` is escaped in every field they encode.
+pub fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char);
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// a scalar text value, serialized as element text (`$value`) so escaping is uniform
+#[derive(Debug, Serialize)]
+pub struct Value(#[serde(rename = "$value")] pub String);
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Self(s.to_owned())
+    }
+}
+
+/// an integer value, serialized as element text
+#[derive(Debug, Serialize)]
+pub struct IntValue(#[serde(rename = "$value")] pub i64);
+
+/// a unit type which always serializes as the S3 XML namespace attribute
+#[derive(Debug, Default)]
+pub struct Xmlns;
+
+impl Serialize for Xmlns {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str("http://s3.amazonaws.com/doc/2006-03-01/")
+    }
+}
+
+/// `<LocationConstraint>`
+#[derive(Debug, Serialize)]
+#[serde(rename = "LocationConstraint")]
+pub struct LocationConstraint {
+    /// xmlns
+    #[serde(rename = "@xmlns")]
+    pub xmlns: Xmlns,
+    /// element text
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+/// `<Owner>`
+#[derive(Debug, Serialize, Default)]
+pub struct Owner {
+    /// ID
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    /// DisplayName
+    #[serde(rename = "DisplayName", skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<Value>,
+}
+
+/// `<Bucket>`
+#[derive(Debug, Serialize)]
+pub struct Bucket {
+    /// Name
+    #[serde(rename = "Name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<Value>,
+    /// CreationDate
+    #[serde(rename = "CreationDate", skip_serializing_if = "Option::is_none")]
+    pub creation_date: Option<Value>,
+}
+
+/// `<Buckets>`
+#[derive(Debug, Serialize)]
+pub struct Buckets {
+    /// Bucket
+    #[serde(rename = "Bucket", default)]
+    pub bucket: Vec<Bucket>,
+}
+
+/// `<ListAllMyBucketsResult>`
+#[derive(Debug, Serialize)]
+#[serde(rename = "ListAllMyBucketsResult")]
+pub struct ListAllMyBucketsResult {
+    /// xmlns
+    #[serde(rename = "@xmlns")]
+    pub xmlns: Xmlns,
+    /// Buckets
+    #[serde(rename = "Buckets", skip_serializing_if = "Option::is_none")]
+    pub buckets: Option<Buckets>,
+    /// Owner
+    #[serde(rename = "Owner", skip_serializing_if = "Option::is_none")]
+    pub owner: Option<Owner>,
+}
+
+/// `<Contents>`
+#[derive(Debug, Serialize)]
+pub struct Contents {
+    /// Key
+    #[serde(rename = "Key", skip_serializing_if = "Option::is_none")]
+    pub key: Option<Value>,
+    /// LastModified
+    #[serde(rename = "LastModified", skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<Value>,
+    /// ETag
+    #[serde(rename = "ETag", skip_serializing_if = "Option::is_none")]
+    pub e_tag: Option<Value>,
+    /// Size
+    #[serde(rename = "Size", skip_serializing_if = "Option::is_none")]
+    pub size: Option<IntValue>,
+    /// StorageClass
+    #[serde(rename = "StorageClass", skip_serializing_if = "Option::is_none")]
+    pub storage_class: Option<Value>,
+    /// Owner
+    #[serde(rename = "Owner", skip_serializing_if = "Option::is_none")]
+    pub owner: Option<Owner>,
+}
+
+/// `<CommonPrefixes>`
+#[derive(Debug, Serialize)]
+pub struct CommonPrefix {
+    /// Prefix
+    #[serde(rename = "Prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<Value>,
+}
+
+/// `<ListBucketResult>`
+#[derive(Debug, Serialize)]
+#[serde(rename = "ListBucketResult")]
+pub struct ListBucketResult {
+    /// xmlns
+    #[serde(rename = "@xmlns")]
+    pub xmlns: Xmlns,
+    /// IsTruncated
+    #[serde(rename = "IsTruncated", skip_serializing_if = "Option::is_none")]
+    pub is_truncated: Option<Value>,
+    /// Marker
+    #[serde(rename = "Marker", skip_serializing_if = "Option::is_none")]
+    pub marker: Option<Value>,
+    /// NextMarker
+    #[serde(rename = "NextMarker", skip_serializing_if = "Option::is_none")]
+    pub next_marker: Option<Value>,
+    /// Contents
+    #[serde(rename = "Contents", default)]
+    pub contents: Vec<Contents>,
+    /// Name
+    #[serde(rename = "Name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<Value>,
+    /// Prefix
+    #[serde(rename = "Prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<Value>,
+    /// Delimiter
+    #[serde(rename = "Delimiter", skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<Value>,
+    /// MaxKeys
+    #[serde(rename = "MaxKeys", skip_serializing_if = "Option::is_none")]
+    pub max_keys: Option<IntValue>,
+    /// CommonPrefixes
+    #[serde(rename = "CommonPrefixes", default)]
+    pub common_prefixes: Vec<CommonPrefix>,
+    /// EncodingType
+    #[serde(rename = "EncodingType", skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<Value>,
+}
+
+/// `<ListBucketResult>` as returned by `ListObjectsV2`
+#[derive(Debug, Serialize)]
+#[serde(rename = "ListBucketResult")]
+pub struct ListBucketResultV2 {
+    /// xmlns
+    #[serde(rename = "@xmlns")]
+    pub xmlns: Xmlns,
+    /// IsTruncated
+    #[serde(rename = "IsTruncated", skip_serializing_if = "Option::is_none")]
+    pub is_truncated: Option<Value>,
+    /// Contents
+    #[serde(rename = "Contents", default)]
+    pub contents: Vec<Contents>,
+    /// Name
+    #[serde(rename = "Name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<Value>,
+    /// Prefix
+    #[serde(rename = "Prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<Value>,
+    /// Delimiter
+    #[serde(rename = "Delimiter", skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<Value>,
+    /// MaxKeys
+    #[serde(rename = "MaxKeys", skip_serializing_if = "Option::is_none")]
+    pub max_keys: Option<IntValue>,
+    /// CommonPrefixes
+    #[serde(rename = "CommonPrefixes", default)]
+    pub common_prefixes: Vec<CommonPrefix>,
+    /// EncodingType
+    #[serde(rename = "EncodingType", skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<Value>,
+    /// KeyCount
+    #[serde(rename = "KeyCount", skip_serializing_if = "Option::is_none")]
+    pub key_count: Option<IntValue>,
+    /// ContinuationToken
+    #[serde(rename = "ContinuationToken", skip_serializing_if = "Option::is_none")]
+    pub continuation_token: Option<Value>,
+    /// NextContinuationToken
+    #[serde(
+        rename = "NextContinuationToken",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub next_continuation_token: Option<Value>,
+    /// StartAfter
+    #[serde(rename = "StartAfter", skip_serializing_if = "Option::is_none")]
+    pub start_after: Option<Value>,
+}
+
+/// `<CopyObjectResult>`
+#[derive(Debug, Serialize)]
+#[serde(rename = "CopyObjectResult")]
+pub struct CopyObjectResult {
+    /// ETag
+    #[serde(rename = "ETag", skip_serializing_if = "Option::is_none")]
+    pub e_tag: Option<Value>,
+    /// LastModified
+    #[serde(rename = "LastModified", skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<Value>,
+}
+
+/// `<Delete>` request body for `DeleteObjects`
+#[derive(Debug, Deserialize)]
+#[serde(rename = "Delete")]
+pub struct Delete {
+    /// Object
+    #[serde(rename = "Object", default)]
+    pub object: Vec<ObjectIdentifier>,
+    /// Quiet
+    #[serde(rename = "Quiet", default)]
+    pub quiet: bool,
+}
+
+/// `<Object>` entry within a `Delete` request
+#[derive(Debug, Deserialize)]
+pub struct ObjectIdentifier {
+    /// Key
+    #[serde(rename = "Key")]
+    pub key: String,
+    /// VersionId
+    #[serde(rename = "VersionId", default)]
+    pub version_id: Option<String>,
+}
+
+/// `<Deleted>` entry within a `DeleteResult`
+#[derive(Debug, Serialize)]
+pub struct DeletedObject {
+    /// Key
+    #[serde(rename = "Key", skip_serializing_if = "Option::is_none")]
+    pub key: Option<Value>,
+}
+
+/// `<Error>` entry within a `DeleteResult`
+#[derive(Debug, Serialize)]
+pub struct DeleteObjectError {
+    /// Key
+    #[serde(rename = "Key", skip_serializing_if = "Option::is_none")]
+    pub key: Option<Value>,
+    /// Code
+    #[serde(rename = "Code", skip_serializing_if = "Option::is_none")]
+    pub code: Option<Value>,
+    /// Message
+    #[serde(rename = "Message", skip_serializing_if = "Option::is_none")]
+    pub message: Option<Value>,
+}
+
+/// `<DeleteResult>`
+#[derive(Debug, Serialize)]
+#[serde(rename = "DeleteResult")]
+pub struct DeleteResult {
+    /// xmlns
+    #[serde(rename = "@xmlns")]
+    pub xmlns: Xmlns,
+    /// Deleted
+    #[serde(rename = "Deleted", default)]
+    pub deleted: Vec<DeletedObject>,
+    /// Error
+    #[serde(rename = "Error", default)]
+    pub error: Vec<DeleteObjectError>,
+}
+
+/// `<Error>`
+#[derive(Debug, Serialize)]
+#[serde(rename = "Error")]
+pub struct ErrorDocument {
+    /// Code
+    #[serde(rename = "Code")]
+    pub code: Value,
+    /// Message
+    #[serde(rename = "Message", skip_serializing_if = "Option::is_none")]
+    pub message: Option<Value>,
+    /// Resource
+    #[serde(rename = "Resource", skip_serializing_if = "Option::is_none")]
+    pub resource: Option<Value>,
+    /// RequestId
+    #[serde(rename = "RequestId", skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<Value>,
+    /// Region
+    #[serde(rename = "Region", skip_serializing_if = "Option::is_none")]
+    pub region: Option<Value>,
+    /// HostId
+    #[serde(rename = "HostId", skip_serializing_if = "Option::is_none")]
+    pub host_id: Option<Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_constraint() {
+        let body = LocationConstraint {
+            xmlns: Xmlns,
+            value: "us-west-2".to_owned(),
+        };
+        assert_eq!(
+            to_xml_with_header(&body).unwrap(),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<LocationConstraint xmlns="http://s3.amazonaws.com/doc/2006-03-01/">us-west-2</LocationConstraint>"#
+            )
+        );
+    }
+
+    #[test]
+    fn list_all_my_buckets_result() {
+        let body = ListAllMyBucketsResult {
+            xmlns: Xmlns,
+            buckets: Some(Buckets {
+                bucket: vec![Bucket {
+                    name: Some("bucket-1".into()),
+                    creation_date: Some("2021-01-01T00:00:00.000Z".into()),
+                }],
+            }),
+            owner: Some(Owner {
+                id: Some("1".into()),
+                display_name: Some("admin".into()),
+            }),
+        };
+        assert_eq!(
+            to_xml_with_header(&body).unwrap(),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<ListAllMyBucketsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#,
+                r#"<Buckets><Bucket><Name>bucket-1</Name><CreationDate>2021-01-01T00:00:00.000Z</CreationDate></Bucket></Buckets>"#,
+                r#"<Owner><ID>1</ID><DisplayName>admin</DisplayName></Owner>"#,
+                r#"</ListAllMyBucketsResult>"#
+            )
+        );
+    }
+
+    #[test]
+    fn list_bucket_result() {
+        let body = ListBucketResult {
+            xmlns: Xmlns,
+            is_truncated: Some("false".into()),
+            marker: None,
+            next_marker: None,
+            contents: vec![Contents {
+                key: Some("a.txt".into()),
+                last_modified: Some("2021-01-01T00:00:00.000Z".into()),
+                e_tag: Some("\"etag\"".into()),
+                size: Some(IntValue(42)),
+                storage_class: Some("STANDARD".into()),
+                owner: None,
+            }],
+            name: Some("my-bucket".into()),
+            prefix: None,
+            delimiter: None,
+            max_keys: Some(IntValue(1000)),
+            common_prefixes: vec![],
+            encoding_type: None,
+        };
+        assert_eq!(
+            to_xml_with_header(&body).unwrap(),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#,
+                r#"<IsTruncated>false</IsTruncated>"#,
+                r#"<Contents><Key>a.txt</Key><LastModified>2021-01-01T00:00:00.000Z</LastModified><ETag>&quot;etag&quot;</ETag><Size>42</Size><StorageClass>STANDARD</StorageClass></Contents>"#,
+                r#"<Name>my-bucket</Name><MaxKeys>1000</MaxKeys>"#,
+                r#"</ListBucketResult>"#
+            )
+        );
+    }
+
+    #[test]
+    fn uri_encode_leaves_unreserved_untouched() {
+        assert_eq!(uri_encode("abcXYZ019-_.~", true), "abcXYZ019-_.~");
+    }
+
+    #[test]
+    fn uri_encode_escapes_reserved_bytes() {
+        assert_eq!(uri_encode("a/b&c d", true), "a%2Fb%26c%20d");
+        assert_eq!(uri_encode("a/b&c d", false), "a/b%26c%20d");
+    }
+
+    #[test]
+    fn copy_object_result() {
+        let body = CopyObjectResult {
+            e_tag: Some("\"etag\"".into()),
+            last_modified: Some("2009-10-12T17:50:30.000Z".into()),
+        };
+        assert_eq!(
+            to_xml_with_header(&body).unwrap(),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<CopyObjectResult><ETag>&quot;etag&quot;</ETag><LastModified>2009-10-12T17:50:30.000Z</LastModified></CopyObjectResult>"#
+            )
+        );
+    }
+
+    #[test]
+    fn delete_request_body() {
+        let xml = concat!(
+            r#"<Delete>"#,
+            r#"<Object><Key>a.txt</Key></Object>"#,
+            r#"<Object><Key>b.txt</Key><VersionId>v1</VersionId></Object>"#,
+            r#"<Quiet>true</Quiet>"#,
+            r#"</Delete>"#
+        );
+        let delete: Delete = from_xml(xml).unwrap();
+        assert!(delete.quiet);
+        assert_eq!(delete.object.len(), 2);
+        assert_eq!(delete.object[0].key, "a.txt");
+        assert_eq!(delete.object[1].version_id.as_deref(), Some("v1"));
+    }
+
+    #[test]
+    fn delete_result() {
+        let body = DeleteResult {
+            xmlns: Xmlns,
+            deleted: vec![DeletedObject {
+                key: Some("a.txt".into()),
+            }],
+            error: vec![DeleteObjectError {
+                key: Some("b.txt".into()),
+                code: Some("AccessDenied".into()),
+                message: Some("Access Denied".into()),
+            }],
+        };
+        assert_eq!(
+            to_xml_with_header(&body).unwrap(),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<DeleteResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#,
+                r#"<Deleted><Key>a.txt</Key></Deleted>"#,
+                r#"<Error><Key>b.txt</Key><Code>AccessDenied</Code><Message>Access Denied</Message></Error>"#,
+                r#"</DeleteResult>"#
+            )
+        );
+    }
+
+    #[test]
+    fn error_document() {
+        let body = ErrorDocument {
+            code: "NoSuchKey".into(),
+            message: Some("The specified key does not exist.".into()),
+            resource: Some("/mybucket/myfoto.jpg".into()),
+            request_id: Some("4442587FB7D0A2F9".into()),
+            region: Some("us-west-2".into()),
+            host_id: Some("host-id".into()),
+        };
+        assert_eq!(
+            to_xml_with_header(&body).unwrap(),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message>"#,
+                r#"<Resource>/mybucket/myfoto.jpg</Resource><RequestId>4442587FB7D0A2F9</RequestId>"#,
+                r#"<Region>us-west-2</Region><HostId>host-id</HostId></Error>"#
+            )
+        );
+    }
+}